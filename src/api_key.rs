@@ -0,0 +1,21 @@
+use deadpool_postgres::Client as PooledClient;
+
+use crate::crypto;
+use crate::error::AppError;
+
+const API_KEY_BYTE_LEN: usize = 32;
+
+/// Gera uma nova chave de API para o cliente, substituindo a anterior (se
+/// houver), e retorna o valor em texto plano. Apenas o hash é persistido, de
+/// modo que a chave não pode ser recuperada depois desta chamada.
+pub async fn issue_api_key(client: &PooledClient, cliente_id: i32) -> Result<String, AppError> {
+    let api_key = crypto::generate_random_token(API_KEY_BYTE_LEN);
+    let api_key_hash = crypto::hash_api_key(&api_key);
+
+    client.execute(
+        "UPDATE clientes SET api_key_hash = $1 WHERE id = $2",
+        &[&api_key_hash, &cliente_id]
+    ).await?;
+
+    Ok(api_key)
+}
@@ -0,0 +1,155 @@
+use actix_web::dev::Payload;
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse, ResponseError};
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::crypto;
+
+/// Configuração do JWT, lida uma única vez a partir das variáveis de ambiente.
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    pub secret: String,
+    pub maxage: i64,
+}
+
+impl JwtConfig {
+    pub fn from_env() -> Self {
+        JwtConfig {
+            secret: env::var("JWT_SECRET").expect("JWT_SECRET deve estar definido"),
+            maxage: env::var("JWT_MAXAGE")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub iat: usize,
+    pub exp: usize,
+}
+
+/// Gera um token HS256 para o cliente informado, válido por `config.maxage` minutos.
+pub fn generate_token(client_id: i32, config: &JwtConfig) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = chrono::Utc::now();
+    let iat = now.timestamp() as usize;
+    let exp = (now + chrono::Duration::minutes(config.maxage)).timestamp() as usize;
+    let claims = Claims {
+        sub: client_id.to_string(),
+        iat,
+        exp,
+    };
+
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(config.secret.as_bytes()),
+    )
+}
+
+fn validate_token(token: &str, config: &JwtConfig) -> Result<Claims, jsonwebtoken::errors::Error> {
+    let data = jsonwebtoken::decode::<Claims>(
+        token,
+        &jsonwebtoken::DecodingKey::from_secret(config.secret.as_bytes()),
+        &jsonwebtoken::Validation::default(),
+    )?;
+    Ok(data.claims)
+}
+
+#[derive(Debug)]
+pub enum AuthError {
+    MissingToken,
+    InvalidToken,
+    ServerMisconfigured,
+    ServiceUnavailable,
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::MissingToken => write!(f, "Token de autenticação ausente"),
+            AuthError::InvalidToken => write!(f, "Token de autenticação inválido ou expirado"),
+            AuthError::ServerMisconfigured => write!(f, "Configuração de autenticação ausente"),
+            AuthError::ServiceUnavailable => write!(f, "Banco de dados não disponível"),
+        }
+    }
+}
+
+impl ResponseError for AuthError {
+    fn error_response(&self) -> HttpResponse {
+        let status = match self {
+            AuthError::ServiceUnavailable => actix_web::http::StatusCode::SERVICE_UNAVAILABLE,
+            _ => actix_web::http::StatusCode::UNAUTHORIZED,
+        };
+
+        HttpResponse::build(status).json(serde_json::json!({
+            "success": false,
+            "message": self.to_string(),
+        }))
+    }
+}
+
+/// Extrator que autentica a requisição a partir do cabeçalho `Authorization: Bearer <token>`
+/// ou, para acesso programático, do cabeçalho `X-Api-Key`.
+pub struct AuthenticatedClient {
+    pub client_id: i32,
+}
+
+impl FromRequest for AuthenticatedClient {
+    type Error = AuthError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let req = req.clone();
+
+        Box::pin(async move {
+            if let Some(api_key) = req
+                .headers()
+                .get("X-Api-Key")
+                .and_then(|h| h.to_str().ok())
+            {
+                let pool = req
+                    .app_data::<web::Data<Pool>>()
+                    .ok_or(AuthError::ServerMisconfigured)?;
+                let client = pool.get().await.map_err(|_| AuthError::ServiceUnavailable)?;
+
+                let api_key_hash = crypto::hash_api_key(api_key);
+                let row = client
+                    .query_opt(
+                        "SELECT id FROM clientes WHERE api_key_hash = $1",
+                        &[&api_key_hash],
+                    )
+                    .await
+                    .map_err(|_| AuthError::ServiceUnavailable)?
+                    .ok_or(AuthError::InvalidToken)?;
+
+                let client_id: i32 = row.get(0);
+                return Ok(AuthenticatedClient { client_id });
+            }
+
+            let config = req
+                .app_data::<web::Data<JwtConfig>>()
+                .ok_or(AuthError::ServerMisconfigured)?;
+
+            let header_value = req
+                .headers()
+                .get("Authorization")
+                .and_then(|h| h.to_str().ok())
+                .ok_or(AuthError::MissingToken)?;
+
+            let token = header_value
+                .strip_prefix("Bearer ")
+                .ok_or(AuthError::MissingToken)?;
+
+            let claims = validate_token(token, config).map_err(|_| AuthError::InvalidToken)?;
+            let client_id: i32 = claims.sub.parse().map_err(|_| AuthError::InvalidToken)?;
+
+            Ok(AuthenticatedClient { client_id })
+        })
+    }
+}
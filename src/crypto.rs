@@ -0,0 +1,44 @@
+use argon2::password_hash::rand_core::RngCore;
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use sha2::{Digest, Sha256};
+
+/// Gera um hash Argon2id (formato PHC) para a senha em texto plano informada.
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    let argon2 = Argon2::default();
+    let hash = argon2.hash_password(password.as_bytes(), &salt)?;
+    Ok(hash.to_string())
+}
+
+/// Verifica se a senha em texto plano corresponde ao hash PHC armazenado.
+///
+/// A comparação é feita pelo próprio Argon2 a partir dos parâmetros
+/// codificados no hash, em tempo constante.
+pub fn verify_password(password: &str, stored_hash: &str) -> Result<bool, argon2::password_hash::Error> {
+    let parsed_hash = PasswordHash::new(stored_hash)?;
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_ok())
+}
+
+/// Gera um token aleatório criptograficamente seguro, codificado em base64url
+/// sem padding. Usado para tokens de verificação de email e chaves de API.
+pub fn generate_random_token(byte_len: usize) -> String {
+    let mut bytes = vec![0u8; byte_len];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Calcula o hash SHA-256 (hex) de uma chave de API.
+///
+/// Diferente das senhas, chaves de API já são aleatórias e de alta entropia,
+/// então um hash rápido e sem salt é suficiente para permitir a busca por
+/// igualdade no banco sem reavaliar o Argon2 a cada requisição.
+pub fn hash_api_key(api_key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(api_key.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
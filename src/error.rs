@@ -0,0 +1,105 @@
+use actix_web::{HttpResponse, ResponseError};
+use thiserror::Error;
+use tokio_postgres::error::SqlState;
+
+use crate::ApiResponse;
+
+/// Erro unificado para os handlers da API, convertido em uma resposta HTTP
+/// consistente por meio de `ResponseError`.
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("Erro de banco de dados: {0}")]
+    Database(String),
+
+    #[error("Email já cadastrado")]
+    EmailExists,
+
+    #[error("CPF já cadastrado")]
+    CpfExists,
+
+    #[error("Email ou senha incorretos")]
+    Unauthorized,
+
+    #[error("Banco de dados não disponível")]
+    ServiceUnavailable,
+
+    #[error("Email ainda não verificado")]
+    EmailNotVerified,
+
+    #[error("Token de verificação inválido ou expirado")]
+    InvalidVerificationToken,
+}
+
+impl ResponseError for AppError {
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            AppError::Database(message) => {
+                eprintln!("Erro de banco de dados: {}", message);
+                HttpResponse::InternalServerError().json(ApiResponse {
+                    success: false,
+                    message: "Erro ao processar a requisição".to_string(),
+                })
+            }
+            AppError::EmailExists => HttpResponse::Conflict().json(ApiResponse {
+                success: false,
+                message: "Email já cadastrado".to_string(),
+            }),
+            AppError::CpfExists => HttpResponse::Conflict().json(ApiResponse {
+                success: false,
+                message: "CPF já cadastrado".to_string(),
+            }),
+            AppError::Unauthorized => HttpResponse::Unauthorized().json(ApiResponse {
+                success: false,
+                message: "Email ou senha incorretos".to_string(),
+            }),
+            AppError::ServiceUnavailable => HttpResponse::ServiceUnavailable().json(ApiResponse {
+                success: false,
+                message: "Banco de dados não disponível".to_string(),
+            }),
+            AppError::EmailNotVerified => HttpResponse::Forbidden().json(ApiResponse {
+                success: false,
+                message: "Email ainda não verificado".to_string(),
+            }),
+            AppError::InvalidVerificationToken => HttpResponse::BadRequest().json(ApiResponse {
+                success: false,
+                message: "Token de verificação inválido ou expirado".to_string(),
+            }),
+        }
+    }
+}
+
+impl From<tokio_postgres::Error> for AppError {
+    fn from(err: tokio_postgres::Error) -> Self {
+        if let Some(db_err) = err.as_db_error() {
+            if *db_err.code() == SqlState::UNIQUE_VIOLATION {
+                let constraint = db_err.constraint().unwrap_or("");
+                if constraint.contains("email") {
+                    return AppError::EmailExists;
+                }
+                if constraint.contains("cpf") {
+                    return AppError::CpfExists;
+                }
+            }
+        }
+        AppError::Database(err.to_string())
+    }
+}
+
+impl From<deadpool_postgres::PoolError> for AppError {
+    fn from(err: deadpool_postgres::PoolError) -> Self {
+        eprintln!("Erro ao obter conexão do pool: {}", err);
+        AppError::ServiceUnavailable
+    }
+}
+
+impl From<argon2::password_hash::Error> for AppError {
+    fn from(err: argon2::password_hash::Error) -> Self {
+        AppError::Database(err.to_string())
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for AppError {
+    fn from(err: jsonwebtoken::errors::Error) -> Self {
+        AppError::Database(err.to_string())
+    }
+}
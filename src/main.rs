@@ -1,11 +1,20 @@
-use actix_web::{web, App, HttpServer, Responder, HttpResponse};
+use actix_web::{web, App, HttpServer, HttpResponse};
 use actix_cors::Cors;
+use deadpool_postgres::{Client as PooledClient, Pool, Runtime};
 use serde::{Deserialize, Serialize};
-use tokio_postgres::{NoTls, Client, Error as PgError};
+use tokio_postgres::{Error as PgError, NoTls};
 use std::env;
+use std::sync::OnceLock;
 use dotenv::dotenv;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+
+mod api_key;
+mod auth;
+mod crypto;
+mod error;
+mod verification;
+
+use auth::{AuthenticatedClient, JwtConfig};
+use error::AppError;
 
 #[derive(Serialize, Deserialize)]
 struct Cliente {
@@ -23,13 +32,18 @@ struct LoginRequest {
 }
 
 #[derive(Serialize, Deserialize)]
-struct ApiResponse {
+struct ResendVerificationRequest {
+    email: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ApiResponse {
     success: bool,
     message: String,
 }
 
 // Função para criar a tabela de clientes se não existir
-async fn create_clientes_table(client: &Client) -> Result<(), PgError> {
+async fn create_clientes_table(client: &PooledClient) -> Result<(), PgError> {
     client.execute(
         "CREATE TABLE IF NOT EXISTS clientes (
             id SERIAL PRIMARY KEY,
@@ -37,243 +51,257 @@ async fn create_clientes_table(client: &Client) -> Result<(), PgError> {
             cpf VARCHAR(14) UNIQUE NOT NULL,
             endereco VARCHAR(200) NOT NULL,
             email VARCHAR(100) UNIQUE NOT NULL,
-            password VARCHAR(100) NOT NULL,
+            password VARCHAR(255) NOT NULL,
+            verified BOOLEAN NOT NULL DEFAULT false,
+            api_key_hash VARCHAR(64) UNIQUE,
             created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
         )",
         &[],
     ).await?;
-    
+
+    // A tabela pode já existir de uma instalação anterior (pré-verificação de
+    // email / chave de API), então as colunas novas são adicionadas à parte.
+    client.execute(
+        "ALTER TABLE clientes ADD COLUMN IF NOT EXISTS verified BOOLEAN NOT NULL DEFAULT false",
+        &[],
+    ).await?;
+    client.execute(
+        "ALTER TABLE clientes ADD COLUMN IF NOT EXISTS api_key_hash VARCHAR(64) UNIQUE",
+        &[],
+    ).await?;
+
     println!("Tabela de clientes verificada/criada com sucesso!");
     Ok(())
 }
 
 async fn register(
     cliente: web::Json<Cliente>,
-    db_client: web::Data<Arc<Mutex<Option<Client>>>>
-) -> impl Responder {
-    let client_lock = db_client.lock().await;
-    
-    if let Some(client) = &*client_lock {
-        // Verificar se o email já existe
-        let email_exists = client.query_one(
-            "SELECT EXISTS(SELECT 1 FROM clientes WHERE email = $1)",
-            &[&cliente.email]
-        ).await;
-        
-        // Verificar se o CPF já existe
-        let cpf_exists = client.query_one(
-            "SELECT EXISTS(SELECT 1 FROM clientes WHERE cpf = $1)",
-            &[&cliente.cpf]
-        ).await;
-        
-        match email_exists {
-            Ok(row) => {
-                let exists: bool = row.get(0);
-                if exists {
-                    return HttpResponse::Conflict().json(ApiResponse {
-                        success: false,
-                        message: "Email já cadastrado".to_string(),
-                    });
-                }
-                
-                match cpf_exists {
-                    Ok(row) => {
-                        let exists: bool = row.get(0);
-                        if exists {
-                            return HttpResponse::Conflict().json(ApiResponse {
-                                success: false,
-                                message: "CPF já cadastrado".to_string(),
-                            });
-                        }
-                        
-                        // Inserir novo cliente
-                        // Nota: Em produção, você deve fazer hash da senha antes de armazenar
-                        let result = client.execute(
-                            "INSERT INTO clientes (nome, cpf, endereco, email, password) VALUES ($1, $2, $3, $4, $5)",
-                            &[&cliente.nome, &cliente.cpf, &cliente.endereco, &cliente.email, &cliente.password]
-                        ).await;
-                        
-                        match result {
-                            Ok(_) => HttpResponse::Ok().json(ApiResponse {
-                                success: true,
-                                message: "Cliente registrado com sucesso".to_string(),
-                            }),
-                            Err(e) => {
-                                eprintln!("Erro ao registrar cliente: {}", e);
-                                HttpResponse::InternalServerError().json(ApiResponse {
-                                    success: false,
-                                    message: "Erro ao registrar cliente".to_string(),
-                                })
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        eprintln!("Erro ao verificar CPF: {}", e);
-                        HttpResponse::InternalServerError().json(ApiResponse {
-                            success: false,
-                            message: "Erro ao verificar CPF".to_string(),
-                        })
-                    }
-                }
-            },
-            Err(e) => {
-                eprintln!("Erro ao verificar email: {}", e);
-                HttpResponse::InternalServerError().json(ApiResponse {
-                    success: false,
-                    message: "Erro ao verificar email".to_string(),
-                })
-            }
-        }
-    } else {
-        HttpResponse::ServiceUnavailable().json(ApiResponse {
-            success: false,
-            message: "Banco de dados não disponível".to_string(),
-        })
+    pool: web::Data<Pool>
+) -> Result<HttpResponse, AppError> {
+    let client = pool.get().await?;
+
+    // Sem pré-checagens de email/CPF: a violação de unicidade do INSERT já
+    // nos diz qual coluna colidiu, via AppError::from(tokio_postgres::Error).
+    // O hash roda em uma thread bloqueante: Argon2id é intencionalmente caro
+    // e não deve travar a reactor thread do Tokio.
+    let password = cliente.password.clone();
+    let password_hash = web::block(move || crypto::hash_password(&password))
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))??;
+
+    let row = client.query_one(
+        "INSERT INTO clientes (nome, cpf, endereco, email, password) VALUES ($1, $2, $3, $4, $5) RETURNING id",
+        &[&cliente.nome, &cliente.cpf, &cliente.endereco, &cliente.email, &password_hash]
+    ).await?;
+    let cliente_id: i32 = row.get(0);
+
+    let token = verification::issue_verification_token(&client, cliente_id).await?;
+    println!("Token de verificação para {}: {}", cliente.email, token);
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        success: true,
+        message: "Cliente registrado com sucesso. Verifique seu email para ativar a conta".to_string(),
+    }))
+}
+
+// Hash Argon2id fixo usado quando o email informado não existe, para que a
+// verificação de senha sempre seja feita (em tempo constante) e o tempo de
+// resposta não denuncie se a conta existe ou não.
+static DUMMY_PASSWORD_HASH: OnceLock<String> = OnceLock::new();
+
+async fn dummy_password_hash() -> Result<String, AppError> {
+    if let Some(hash) = DUMMY_PASSWORD_HASH.get() {
+        return Ok(hash.clone());
     }
+
+    let hash = web::block(|| crypto::hash_password("senha-fixa-para-comparacao-em-tempo-constante"))
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))??;
+
+    Ok(DUMMY_PASSWORD_HASH.get_or_init(|| hash).clone())
 }
 
 async fn login(
     login_req: web::Json<LoginRequest>,
-    db_client: web::Data<Arc<Mutex<Option<Client>>>>
-) -> impl Responder {
-    let client_lock = db_client.lock().await;
-    
-    if let Some(client) = &*client_lock {
-        // Verificar credenciais
-        let result = client.query_opt(
-            "SELECT id, nome FROM clientes WHERE email = $1 AND password = $2",
-            &[&login_req.email, &login_req.password]
-        ).await;
-        
-        match result {
-            Ok(row_option) => {
-                if let Some(row) = row_option {
-                    // Cliente encontrado, login bem-sucedido
-                    let id: i32 = row.get(0);
-                    let nome: String = row.get(1);
-                    
-                    HttpResponse::Ok().json(serde_json::json!({
-                        "success": true,
-                        "message": "Login bem-sucedido",
-                        "cliente": {
-                            "id": id,
-                            "nome": nome
-                        }
-                    }))
-                } else {
-                    // Cliente não encontrado ou senha incorreta
-                    HttpResponse::Unauthorized().json(ApiResponse {
-                        success: false,
-                        message: "Email ou senha incorretos".to_string(),
-                    })
-                }
-            },
-            Err(e) => {
-                eprintln!("Erro ao verificar credenciais: {}", e);
-                HttpResponse::InternalServerError().json(ApiResponse {
-                    success: false,
-                    message: "Erro ao verificar credenciais".to_string(),
-                })
-            }
-        }
-    } else {
-        HttpResponse::ServiceUnavailable().json(ApiResponse {
-            success: false,
-            message: "Banco de dados não disponível".to_string(),
-        })
+    pool: web::Data<Pool>,
+    jwt_config: web::Data<JwtConfig>,
+) -> Result<HttpResponse, AppError> {
+    let client = pool.get().await?;
+
+    let row = client.query_opt(
+        "SELECT id, nome, password, verified FROM clientes WHERE email = $1",
+        &[&login_req.email]
+    ).await?;
+
+    // A senha é sempre verificada, mesmo quando o email não existe (contra um
+    // hash fixo), para que o tempo de resposta não revele se a conta existe.
+    let password_hash = match &row {
+        Some(row) => row.get::<_, String>(2),
+        None => dummy_password_hash().await?,
+    };
+
+    let password = login_req.password.clone();
+    let password_matches = web::block(move || crypto::verify_password(&password, &password_hash))
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))??;
+
+    let row = row.filter(|_| password_matches).ok_or(AppError::Unauthorized)?;
+
+    let id: i32 = row.get(0);
+    let nome: String = row.get(1);
+    let verified: bool = row.get(3);
+
+    if !verified {
+        return Err(AppError::EmailNotVerified);
     }
+
+    let token = auth::generate_token(id, &jwt_config)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "success": true,
+        "message": "Login bem-sucedido",
+        "token": token,
+        "cliente": {
+            "id": id,
+            "nome": nome
+        }
+    })))
 }
 
 async fn list_clientes(
-    db_client: web::Data<Arc<Mutex<Option<Client>>>>
-) -> impl Responder {
-    let client_lock = db_client.lock().await;
-    
-    if let Some(client) = &*client_lock {
-        match client.query("SELECT id, nome, cpf, endereco, email, created_at FROM clientes ORDER BY id", &[]).await {
-            Ok(rows) => {
-                let clientes: Vec<serde_json::Value> = rows
-                    .iter()
-                    .map(|row| {
-                        let id: i32 = row.get(0);
-                        let nome: String = row.get(1);
-                        let cpf: String = row.get(2);
-                        let endereco: String = row.get(3);
-                        let email: String = row.get(4);
-                        // Cambiamos el tipo a NaiveDateTime
-                        let created_at: chrono::NaiveDateTime = row.get(5);
-                        
-                        serde_json::json!({
-                            "id": id,
-                            "nome": nome,
-                            "cpf": cpf,
-                            "endereco": endereco,
-                            "email": email,
-                            "created_at": created_at.to_string()
-                        })
-                    })
-                    .collect();
-                
-                HttpResponse::Ok().json(clientes)
-            },
-            Err(e) => {
-                eprintln!("Erro ao listar clientes: {}", e);
-                HttpResponse::InternalServerError().json(serde_json::json!({
-                    "success": false,
-                    "message": "Erro ao listar clientes"
-                }))
-            }
-        }
-    } else {
-        HttpResponse::ServiceUnavailable().json(serde_json::json!({
-            "success": false,
-            "message": "Banco de dados não disponível"
-        }))
+    _authenticated: AuthenticatedClient,
+    pool: web::Data<Pool>
+) -> Result<HttpResponse, AppError> {
+    let client = pool.get().await?;
+
+    let rows = client.query(
+        "SELECT id, nome, cpf, endereco, email, created_at FROM clientes ORDER BY id",
+        &[]
+    ).await?;
+
+    let clientes: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| {
+            let id: i32 = row.get(0);
+            let nome: String = row.get(1);
+            let cpf: String = row.get(2);
+            let endereco: String = row.get(3);
+            let email: String = row.get(4);
+            // Cambiamos el tipo a NaiveDateTime
+            let created_at: chrono::NaiveDateTime = row.get(5);
+
+            serde_json::json!({
+                "id": id,
+                "nome": nome,
+                "cpf": cpf,
+                "endereco": endereco,
+                "email": email,
+                "created_at": created_at.to_string()
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(clientes))
+}
+
+async fn verify_email(
+    token: web::Path<String>,
+    pool: web::Data<Pool>
+) -> Result<HttpResponse, AppError> {
+    let client = pool.get().await?;
+
+    verification::confirm_verification_token(&client, &token).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        success: true,
+        message: "Email verificado com sucesso".to_string(),
+    }))
+}
+
+async fn resend_verification(
+    req: web::Json<ResendVerificationRequest>,
+    pool: web::Data<Pool>
+) -> Result<HttpResponse, AppError> {
+    let client = pool.get().await?;
+
+    // Não revelamos se o email existe ou não: a resposta é a mesma em ambos
+    // os casos, só o envio do token é pulado quando não há cliente (mesmo
+    // princípio aplicado ao "Email ou senha incorretos" do login).
+    if let Some(row) = client.query_opt(
+        "SELECT id FROM clientes WHERE email = $1",
+        &[&req.email]
+    ).await? {
+        let cliente_id: i32 = row.get(0);
+        let token = verification::issue_verification_token(&client, cliente_id).await?;
+        println!("Token de verificação para {}: {}", req.email, token);
     }
+
+    Ok(HttpResponse::Ok().json(ApiResponse {
+        success: true,
+        message: "Se o email estiver cadastrado, um novo link de verificação foi enviado".to_string(),
+    }))
+}
+
+async fn issue_api_key(
+    authenticated: AuthenticatedClient,
+    pool: web::Data<Pool>
+) -> Result<HttpResponse, AppError> {
+    let client = pool.get().await?;
+    let api_key = api_key::issue_api_key(&client, authenticated.client_id).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "success": true,
+        "message": "Chave de API gerada com sucesso",
+        "api_key": api_key
+    })))
+}
+
+// Rotacionar é a mesma operação de emitir uma chave nova: a anterior é
+// substituída de qualquer forma, então ambas as rotas chamam o mesmo handler.
+async fn rotate_api_key(
+    authenticated: AuthenticatedClient,
+    pool: web::Data<Pool>
+) -> Result<HttpResponse, AppError> {
+    issue_api_key(authenticated, pool).await
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
-    
-    // Try to get DATABASE_URL, but don't panic if it's not found
-    let database_url = env::var("DATABASE_URL");
-    
-    let client_option = if let Ok(url) = database_url {
-        // Conectar ao banco de dados
-        println!("Tentando conectar ao banco de dados...");
-        match tokio_postgres::connect(&url, NoTls).await {
-            Ok((client, connection)) => {
-                println!("Conexão com o banco de dados estabelecida com sucesso!");
-                
-                // Iniciar a conexão em um thread separado
-                tokio::spawn(async move {
-                    if let Err(e) = connection.await {
-                        eprintln!("Erro na conexão: {}", e);
-                    }
-                });
-                
-                // Criar tabela de clientes se não existir
-                if let Err(e) = create_clientes_table(&client).await {
-                    eprintln!("Erro ao criar tabela de clientes: {}", e);
-                }
-                
-                Some(client)
-            },
-            Err(e) => {
-                eprintln!("Erro ao conectar ao banco de dados: {}", e);
-                eprintln!("Verifique se o PostgreSQL está em execução e se a URL de conexão está correta.");
-                eprintln!("Continuando sem conexão com o banco de dados...");
-                None
+
+    // Configuração do JWT usada para emitir e validar os tokens de sessão
+    let jwt_config = web::Data::new(JwtConfig::from_env());
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL deve estar definido");
+
+    let mut pg_config = deadpool_postgres::Config::new();
+    pg_config.url = Some(database_url);
+    let pool = pg_config
+        .create_pool(Some(Runtime::Tokio1), NoTls)
+        .expect("Erro ao criar o pool de conexões com o banco de dados");
+
+    // A pool é preguiçosa: uma conexão só é aberta de fato na primeira chamada
+    // a `pool.get()`. Se o banco estiver fora do ar, isso aparece como erro de
+    // aquisição nos handlers, que respondem com 503 em vez de travar o servidor.
+    match pool.get().await {
+        Ok(client) => {
+            println!("Conexão com o banco de dados estabelecida com sucesso!");
+            if let Err(e) = create_clientes_table(&client).await {
+                eprintln!("Erro ao criar tabela de clientes: {}", e);
             }
+            if let Err(e) = verification::create_verification_tokens_table(&client).await {
+                eprintln!("Erro ao criar tabela de tokens de verificação: {}", e);
+            }
+        },
+        Err(e) => {
+            eprintln!("Erro ao conectar ao banco de dados: {}", e);
+            eprintln!("Verifique se o PostgreSQL está em execução e se a URL de conexão está correta.");
+            eprintln!("Continuando - novas tentativas serão feitas a cada requisição...");
         }
-    } else {
-        eprintln!("DATABASE_URL não está definido. Continuando sem conexão com o banco de dados...");
-        None
-    };
+    }
 
-    // Compartilhar o cliente do banco de dados entre as rotas
-    let db_client = web::Data::new(Arc::new(Mutex::new(client_option)));
+    // Compartilhar o pool de conexões entre as rotas
+    let pool = web::Data::new(pool);
 
     println!("Iniciando servidor HTTP em 127.0.0.1:8080");
     HttpServer::new(move || {
@@ -283,13 +311,18 @@ async fn main() -> std::io::Result<()> {
             .allow_any_method()
             .allow_any_header()
             .max_age(3600);
-            
+
         App::new()
             .wrap(cors)  // Adiciona o middleware CORS
-            .app_data(db_client.clone())
+            .app_data(pool.clone())
+            .app_data(jwt_config.clone())
             .route("/login", web::post().to(login))
             .route("/register", web::post().to(register))
             .route("/clientes", web::get().to(list_clientes))
+            .route("/verify/{token}", web::get().to(verify_email))
+            .route("/resend-verification", web::post().to(resend_verification))
+            .route("/api-key", web::post().to(issue_api_key))
+            .route("/api-key/rotate", web::post().to(rotate_api_key))
     })
     .bind("127.0.0.1:8080")?
     .run()
@@ -0,0 +1,70 @@
+use chrono::{Duration, Utc};
+use deadpool_postgres::Client as PooledClient;
+
+use crate::crypto;
+use crate::error::AppError;
+
+const TOKEN_BYTE_LEN: usize = 32;
+const TOKEN_VALIDITY_HOURS: i64 = 24;
+
+/// Função para criar a tabela de tokens de verificação se não existir
+pub async fn create_verification_tokens_table(client: &PooledClient) -> Result<(), tokio_postgres::Error> {
+    client.execute(
+        "CREATE TABLE IF NOT EXISTS verification_tokens (
+            id SERIAL PRIMARY KEY,
+            cliente_id INTEGER NOT NULL REFERENCES clientes(id),
+            token VARCHAR(64) UNIQUE NOT NULL,
+            expires_at TIMESTAMP NOT NULL,
+            used BOOLEAN NOT NULL DEFAULT false,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )",
+        &[],
+    ).await?;
+
+    println!("Tabela de tokens de verificação verificada/criada com sucesso!");
+    Ok(())
+}
+
+/// Gera um token de verificação para o cliente e o persiste com validade de
+/// `TOKEN_VALIDITY_HOURS` horas.
+pub async fn issue_verification_token(client: &PooledClient, cliente_id: i32) -> Result<String, AppError> {
+    let token = crypto::generate_random_token(TOKEN_BYTE_LEN);
+    let expires_at = (Utc::now() + Duration::hours(TOKEN_VALIDITY_HOURS)).naive_utc();
+
+    client.execute(
+        "INSERT INTO verification_tokens (cliente_id, token, expires_at) VALUES ($1, $2, $3)",
+        &[&cliente_id, &token, &expires_at]
+    ).await?;
+
+    Ok(token)
+}
+
+/// Valida o token informado e, se válido, marca o cliente correspondente
+/// como verificado. Retorna o id do cliente verificado.
+pub async fn confirm_verification_token(client: &PooledClient, token: &str) -> Result<i32, AppError> {
+    let row = client.query_opt(
+        "SELECT cliente_id, expires_at, used FROM verification_tokens WHERE token = $1",
+        &[&token]
+    ).await?
+        .ok_or(AppError::InvalidVerificationToken)?;
+
+    let cliente_id: i32 = row.get(0);
+    let expires_at: chrono::NaiveDateTime = row.get(1);
+    let used: bool = row.get(2);
+
+    if used || expires_at < Utc::now().naive_utc() {
+        return Err(AppError::InvalidVerificationToken);
+    }
+
+    client.execute(
+        "UPDATE verification_tokens SET used = true WHERE token = $1",
+        &[&token]
+    ).await?;
+
+    client.execute(
+        "UPDATE clientes SET verified = true WHERE id = $1",
+        &[&cliente_id]
+    ).await?;
+
+    Ok(cliente_id)
+}